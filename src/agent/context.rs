@@ -7,11 +7,13 @@
 //! Memory Hierarchy:
 //!   System Prompt  →  "L1 cache" (never evicted)
 //!   Recent Messages →  "RAM" (the context window)
-//!   Evicted History →  "Disk" (would go to vector store in full impl)
+//!   Evicted History →  "Disk" (semantic vector store, see `vector_store`)
 
 use alloc::collections::VecDeque;
 use alloc::string::String;
+use alloc::vec::Vec;
 use alloc::format;
+use crate::agent::vector_store::VectorStore;
 
 /// Role of a message in the context window.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,6 +49,9 @@ pub struct ContextWindow {
     max_tokens: usize,
     current_tokens: usize,
     total_evicted: usize,
+    /// The "Disk" tier: evicted messages, embedded and kept for semantic
+    /// recall instead of being discarded.
+    disk: VectorStore,
 }
 
 impl ContextWindow {
@@ -57,6 +62,7 @@ impl ContextWindow {
             max_tokens,
             current_tokens: 0,
             total_evicted: 0,
+            disk: VectorStore::new(),
         }
     }
 
@@ -70,15 +76,16 @@ impl ContextWindow {
 
         // Eviction loop: make room if needed
         while self.current_tokens + token_count > self.max_tokens {
+            let _span = crate::trace_span!("context_evict");
             if let Some(evicted) = self.evict_oldest() {
                 self.total_evicted += 1;
-                // In full implementation: summarize and store in vector DB
                 crate::serial_println!(
-                    "[context] Evicted {} message ({} tokens). Total evicted: {}",
+                    "[context] Evicted {} message ({} tokens) to disk. Total evicted: {}",
                     evicted.role.as_str(),
                     evicted.token_count,
                     self.total_evicted
                 );
+                self.disk.store(evicted);
             } else {
                 break; // Only system prompt remains, can't evict
             }
@@ -120,6 +127,41 @@ impl ContextWindow {
         self.messages.len()
     }
 
+    /// Snapshot the current messages as `(role, content)` pairs, in order —
+    /// the shape the inference transport serializes into a request.
+    pub fn messages_for_inference(&self) -> Vec<(Role, String)> {
+        self.messages
+            .iter()
+            .map(|m| (m.role.clone(), m.content.clone()))
+            .collect()
+    }
+
+    /// Search the disk tier for the `k` evicted messages most semantically
+    /// similar to `query`, formatted for display.
+    pub fn recall_semantic(&self, query: &str, k: usize) -> String {
+        let hits = self.disk.top_k(query, k);
+        if hits.is_empty() {
+            return format!("No evicted messages match '{}' ({} on disk)", query, self.disk.len());
+        }
+
+        let mut out = format!("Top {} semantic matches for '{}':\n", hits.len(), query);
+        for (message, score) in hits {
+            out.push_str(&format!("  [{:.3}] ({}) {}\n", score, message.role.as_str(), message.content));
+        }
+        out
+    }
+
+    /// The single evicted message most semantically relevant to `query`, if
+    /// the disk tier has anything close enough to be worth surfacing.
+    pub fn top_semantic_hint(&self, query: &str) -> Option<String> {
+        self.disk
+            .top_k(query, 1)
+            .into_iter()
+            .next()
+            .filter(|(_, score)| *score > 0.0)
+            .map(|(message, _)| message.content.clone())
+    }
+
     /// Get a human-readable status string.
     pub fn status(&self) -> String {
         format!(
@@ -137,7 +179,7 @@ impl ContextWindow {
 }
 
 /// Rough token estimation: ~4 characters per token (OpenAI's rule of thumb).
-fn estimate_tokens(text: &str) -> usize {
+pub(crate) fn estimate_tokens(text: &str) -> usize {
     let count = text.len() / 4;
     if count == 0 { 1 } else { count }
 }
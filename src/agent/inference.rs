@@ -0,0 +1,328 @@
+//! Remote inference — talks to an LLM endpoint over a pluggable transport.
+//!
+//! `Agent::process_input` used to be a placeholder echo "until network
+//! drivers enable remote inference." This module is that inference
+//! subsystem: a `Transport` carries a JSON-RPC `infer` request built from
+//! the current `ContextWindow` out to wherever the model actually runs, and
+//! parses back a structured response — assistant text, any tool calls the
+//! model wants run, and a `logs` trace of its intermediate reasoning.
+//!
+//! There's no `serde` in a `no_std` kernel build, so the request/response
+//! JSON is built and parsed by hand below, scoped to exactly the shape this
+//! protocol uses rather than general-purpose JSON.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::format;
+use crate::agent::context::Role;
+
+/// Carries a serialized `infer` request to the model and returns its raw
+/// JSON response. `SerialTransport` is the first implementation; a network
+/// transport can implement the same trait once network drivers land.
+pub trait Transport {
+    fn send(&mut self, request: &str) -> Result<String, String>;
+}
+
+/// Sends inference requests over the kernel's serial port, one JSON object
+/// per line. This is the bring-up transport used before network drivers
+/// exist — a host-side process on the other end of the serial line runs the
+/// actual model and writes back a single-line JSON response.
+pub struct SerialTransport;
+
+impl Transport for SerialTransport {
+    fn send(&mut self, request: &str) -> Result<String, String> {
+        crate::serial_println!("{}", request);
+        crate::serial::read_line().ok_or_else(|| String::from("no response on serial transport"))
+    }
+}
+
+/// A tool call the model asked the agent to perform.
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub name: String,
+    pub args: String,
+}
+
+/// A parsed `infer` response.
+#[derive(Debug, Clone)]
+pub struct InferenceResponse {
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    /// Diagnostic trace of the model's intermediate reasoning / tool
+    /// dispatch, surfaced to the user alongside the final answer rather
+    /// than discarded.
+    pub logs: Vec<String>,
+}
+
+/// Build a JSON-RPC `infer` request from the context window's messages.
+pub fn build_request(messages: &[(Role, String)]) -> String {
+    let mut parts = Vec::with_capacity(messages.len());
+    for (role, content) in messages {
+        parts.push(format!(
+            "{{\"role\":\"{}\",\"content\":\"{}\"}}",
+            role.as_str(),
+            escape_json(content)
+        ));
+    }
+    format!(
+        "{{\"method\":\"infer\",\"params\":{{\"messages\":[{}]}}}}",
+        parts.join(",")
+    )
+}
+
+/// Run one inference round-trip: serialize `messages`, send over
+/// `transport`, and parse the structured response.
+pub fn infer(transport: &mut dyn Transport, messages: &[(Role, String)]) -> Result<InferenceResponse, String> {
+    let request = build_request(messages);
+    let raw = transport.send(&request)?;
+    parse_response(&raw)
+}
+
+fn escape_json(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+// ─── Minimal JSON parsing ──────────────────────────────────────────────
+//
+// Only covers the shapes the `infer` response can take: objects, arrays,
+// and strings. Numbers/booleans/null are not part of this protocol and are
+// rejected rather than silently accepted.
+
+#[derive(Debug, Clone)]
+enum Json {
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Parser { bytes: s.as_bytes(), pos: 0 }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == b' ' || b == b'\t' || b == b'\n' || b == b'\r' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), String> {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at byte {}", b as char, self.pos))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_ws();
+        match self.peek() {
+            Some(b'"') => self.parse_string().map(Json::Str),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            _ => Err(format!("unsupported or truncated JSON value at byte {}", self.pos)),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        // Buffer raw bytes and decode once at the end, rather than widening
+        // each byte to a `char` — a multi-byte UTF-8 sequence's continuation
+        // bytes never match `"` or `\`, so they pass through untouched here.
+        let mut raw = Vec::new();
+        loop {
+            let b = *self
+                .bytes
+                .get(self.pos)
+                .ok_or_else(|| String::from("unterminated JSON string"))?;
+            self.pos += 1;
+            match b {
+                b'"' => {
+                    return String::from_utf8(raw)
+                        .map_err(|_| String::from("invalid utf8 in JSON string"));
+                }
+                b'\\' => {
+                    let esc = *self
+                        .bytes
+                        .get(self.pos)
+                        .ok_or_else(|| String::from("unterminated JSON escape"))?;
+                    self.pos += 1;
+                    raw.push(match esc {
+                        b'"' => b'"',
+                        b'\\' => b'\\',
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        other => other,
+                    });
+                }
+                _ => raw.push(b),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Arr(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                }
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(format!("expected ',' or ']' at byte {}", self.pos)),
+            }
+        }
+        Ok(Json::Arr(items))
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.expect(b'{')?;
+        let mut fields = Vec::new();
+        self.skip_ws();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Obj(fields));
+        }
+        loop {
+            self.skip_ws();
+            let key = self.parse_string()?;
+            self.skip_ws();
+            self.expect(b':')?;
+            let value = self.parse_value()?;
+            fields.push((key, value));
+            self.skip_ws();
+            match self.peek() {
+                Some(b',') => {
+                    self.pos += 1;
+                }
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(format!("expected ',' or '}}' at byte {}", self.pos)),
+            }
+        }
+        Ok(Json::Obj(fields))
+    }
+}
+
+impl Json {
+    fn field(&self, name: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(fields) => fields.iter().find(|(k, _)| k == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    fn as_arr(&self) -> Option<&[Json]> {
+        match self {
+            Json::Arr(a) => Some(a.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a raw `infer` response body into its three fields.
+fn parse_response(raw: &str) -> Result<InferenceResponse, String> {
+    let root = Parser::new(raw).parse_value()?;
+
+    let content = root
+        .field("content")
+        .and_then(Json::as_str)
+        .map(String::from)
+        .unwrap_or_default();
+
+    let tool_calls = root
+        .field("tool_calls")
+        .and_then(Json::as_arr)
+        .unwrap_or(&[])
+        .iter()
+        .map(|call| {
+            let name = call.field("name").and_then(Json::as_str).unwrap_or_default();
+            let args = call.field("args").and_then(Json::as_str).unwrap_or_default();
+            ToolCall {
+                name: String::from(name),
+                args: String::from(args),
+            }
+        })
+        .collect();
+
+    let logs = root
+        .field("logs")
+        .and_then(Json::as_arr)
+        .unwrap_or(&[])
+        .iter()
+        .filter_map(Json::as_str)
+        .map(String::from)
+        .collect();
+
+    Ok(InferenceResponse { content, tool_calls, logs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A multi-byte UTF-8 sequence's continuation bytes never match `"` or
+    /// `\`, so `parse_string` must pass them through untouched rather than
+    /// widening each byte to a `char` (which would split them apart).
+    #[test_case]
+    fn parse_response_decodes_multibyte_utf8_content() {
+        let response = parse_response("{\"content\":\"caf\u{e9} \u{1f980} \u{65e5}\u{672c}\u{8a9e}\"}").unwrap();
+        assert_eq!(response.content, "caf\u{e9} \u{1f980} \u{65e5}\u{672c}\u{8a9e}");
+    }
+
+    /// Round-trips the same content through `build_request`'s escaping and
+    /// back through `parse_response`, the full path a real message takes.
+    #[test_case]
+    fn build_request_round_trips_multibyte_content() {
+        let content = String::from("caf\u{e9} \u{1f980}");
+        let request = build_request(&[(Role::User, content.clone())]);
+        let root = Parser::new(&request).parse_value().unwrap();
+        let messages = root.field("params").unwrap().field("messages").unwrap().as_arr().unwrap();
+        assert_eq!(messages[0].field("content").unwrap().as_str().unwrap(), content);
+    }
+}
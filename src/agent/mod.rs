@@ -4,20 +4,37 @@
 //! invoke tools → emit output. In the LLM OS, the agent IS the userland.
 //!
 //! Submodules:
-//! - `context` — Context window (the agent's working memory / "RAM")
-//! - `tools`   — Tool registry (the agent's "syscall table")
+//! - `context`      — Context window (the agent's working memory / "RAM")
+//! - `tools`        — Tool registry (the agent's "syscall table")
+//! - `inference`    — Remote inference transport and tool-call loop
+//! - `vector_store` — Semantic "disk" tier for evicted context messages
+//!
+//! `process_input`'s phases are instrumented with `crate::tracer` — see
+//! the `/trace` command below.
 
 pub mod context;
+pub mod inference;
 pub mod tools;
+pub mod vector_store;
+pub mod wasm;
 
+use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
-use crate::{println, serial_println};
+use crate::{println, serial_println, tracer};
+
+/// Upper bound on tool-call round-trips within a single `process_input`
+/// call, so a model that never stops requesting tools can't hang the agent.
+const MAX_INFERENCE_ROUNDS: usize = 8;
 
 /// The Agent — the single "super process" that is the LLM OS userland.
 pub struct Agent {
     pub context: context::ContextWindow,
     pub tool_registry: tools::ToolRegistry,
+    /// Set once a transport is connected; `process_input` routes through
+    /// real inference when present and falls back to the command parser
+    /// otherwise.
+    transport: Option<Box<dyn inference::Transport>>,
 }
 
 impl Agent {
@@ -26,9 +43,16 @@ impl Agent {
         Agent {
             context: context::ContextWindow::new(4096), // 4K token context
             tool_registry: tools::ToolRegistry::new(),
+            transport: None,
         }
     }
 
+    /// Connect an inference transport. Until this is called, `process_input`
+    /// uses the placeholder command parser.
+    pub fn connect_transport(&mut self, transport: Box<dyn inference::Transport>) {
+        self.transport = Some(transport);
+    }
+
     /// Boot the agent — load system prompt and register built-in tools.
     pub fn boot(&mut self) {
         serial_println!("[agent] Booting agent...");
@@ -48,6 +72,8 @@ impl Agent {
         self.tool_registry.register(tools::BuiltinTool::MemoryStore);
         self.tool_registry.register(tools::BuiltinTool::MemoryRecall);
         self.tool_registry.register(tools::BuiltinTool::Echo);
+        self.tool_registry.register(tools::BuiltinTool::RecallSemantic);
+        self.tool_registry.register(tools::BuiltinTool::Send);
 
         serial_println!(
             "[agent] {} tools registered: {:?}",
@@ -61,54 +87,157 @@ impl Agent {
 
     /// Process a single input message and return the agent's response.
     ///
-    /// In a full implementation, this would:
-    /// 1. Add the input to the context window
-    /// 2. Send the context to an LLM inference endpoint
-    /// 3. Parse tool calls from the response
-    /// 4. Execute tools and feed results back
-    /// 5. Return the final response
-    ///
-    /// For now, we implement a simple command parser as a placeholder
-    /// until network drivers enable remote inference.
+    /// When a transport is connected (see `connect_transport`), this sends
+    /// the context window to the model, executes any requested tool calls,
+    /// and re-invokes inference until the model stops asking for tools.
+    /// Otherwise it falls back to the placeholder command parser used
+    /// before any transport exists.
     pub fn process_input(&mut self, input: &str) -> String {
-        self.context.push_message(context::Role::User, input);
+        let _span = crate::trace_span!("process_input");
+
+        {
+            let _span = crate::trace_span!("context_push");
+            self.context.push_message(context::Role::User, input);
+        }
         serial_println!("[agent] Processing input: {}", input);
 
-        // Simple command parsing (placeholder for LLM inference)
-        let response = if input.starts_with("/tool ") {
+        if input.starts_with("/trace") {
+            let response = tracer::dump(20);
+            self.context.push_message(context::Role::Assistant, &response);
+            return response;
+        }
+
+        let response = if self.transport.is_some() {
+            // Auto-retrieval: pull the most relevant evicted message back
+            // into the live context before inference sees it, rather than
+            // requiring an explicit /tool recall_semantic call every time.
+            if let Some(hint) = self.context.top_semantic_hint(input) {
+                // Role::Tool, not Role::System — System messages are the
+                // "L1 cache" tier evict_oldest never touches, and this hint
+                // is exactly the kind of thing the disk tier should be free
+                // to reclaim once it scrolls out of the window again.
+                self.context.push_message(
+                    context::Role::Tool,
+                    &alloc::format!("Relevant earlier context: {}", hint),
+                );
+            }
+            self.run_inference_loop()
+        } else {
+            self.run_command_parser(input)
+        };
+
+        self.context.push_message(context::Role::Assistant, &response);
+        response
+    }
+
+    /// Placeholder command parser used until a transport is connected.
+    fn run_command_parser(&mut self, input: &str) -> String {
+        if input.starts_with("/tool ") {
             let tool_input = &input[6..];
             self.handle_tool_call(tool_input)
+        } else if input.starts_with("/send ") {
+            // Passed straight through as the response — the orchestrator
+            // (see `crate::orchestrator::parse_send_command`) is what
+            // actually parses and routes this; the agent itself has no
+            // notion of other agents or their ids.
+            String::from(input)
         } else if input.starts_with("/context") {
             self.context.status()
         } else if input.starts_with("/help") {
             String::from(
                 "Available commands:\n\
                  /tool <name> <args>  — invoke a tool\n\
+                 /send <agent> <msg>  — delegate to another agent (orchestrator only)\n\
                  /context             — show context window status\n\
+                 /trace               — dump recent trace spans (durations, token cost)\n\
                  /help                — show this help\n\
                  (anything else)      — echoed back (LLM inference not yet connected)"
             )
         } else {
             // Echo back (placeholder until inference is connected)
             alloc::format!("[echo] {}", input)
-        };
+        }
+    }
 
-        self.context.push_message(context::Role::Assistant, &response);
-        response
+    /// Drive inference to completion: send the context, execute any
+    /// requested tool calls and feed results back, and repeat until the
+    /// model returns no further tool calls (or the round limit is hit).
+    fn run_inference_loop(&mut self) -> String {
+        let mut content = String::new();
+        let mut all_logs: Vec<String> = Vec::new();
+
+        for _ in 0..MAX_INFERENCE_ROUNDS {
+            let messages = self.context.messages_for_inference();
+            let transport = self
+                .transport
+                .as_deref_mut()
+                .expect("run_inference_loop called with no transport connected");
+
+            let response = match inference::infer(transport, &messages) {
+                Ok(r) => r,
+                Err(e) => return alloc::format!("Inference error: {}", e),
+            };
+
+            for log in &response.logs {
+                serial_println!("[inference] {}", log);
+                self.context.push_message(context::Role::Tool, &alloc::format!("[log] {}", log));
+                all_logs.push(log.clone());
+            }
+
+            content = response.content;
+
+            if response.tool_calls.is_empty() {
+                break;
+            }
+
+            // This round produced intermediate reasoning ahead of a tool
+            // dispatch rather than a final answer — record it now, since
+            // only the loop's last round's content becomes the return value
+            // `process_input` pushes as the Assistant message itself.
+            if !content.is_empty() {
+                self.context.push_message(context::Role::Assistant, &content);
+            }
+
+            for call in &response.tool_calls {
+                let result = self.execute_tool(&call.name, &call.args);
+                self.context.push_message(context::Role::Tool, &result);
+            }
+        }
+
+        if all_logs.is_empty() {
+            content
+        } else {
+            alloc::format!("[trace]\n{}\n\n{}", all_logs.join("\n"), content)
+        }
     }
 
-    /// Parse and execute a tool call.
+    /// Parse and execute a `/tool <name> <args>` command.
     fn handle_tool_call(&mut self, input: &str) -> String {
         let parts: Vec<&str> = input.splitn(2, ' ').collect();
         let tool_name = parts.get(0).unwrap_or(&"");
         let tool_args = parts.get(1).unwrap_or(&"");
 
-        match self.tool_registry.execute(tool_name, tool_args) {
-            Ok(result) => {
-                self.context.push_message(context::Role::Tool, &result);
-                result
+        let result = self.execute_tool(tool_name, tool_args);
+        self.context.push_message(context::Role::Tool, &result);
+        result
+    }
+
+    /// Execute a tool by name, routing `recall_semantic` to the context
+    /// window's vector store directly (it needs access `ToolRegistry`
+    /// doesn't have) and everything else through the registry.
+    fn execute_tool(&mut self, name: &str, args: &str) -> String {
+        let mut span = crate::trace_span!("tool_exec", name);
+
+        let result = if name == "recall_semantic" {
+            self.context.recall_semantic(args.trim(), 3)
+        } else {
+            match self.tool_registry.execute(name, args) {
+                Ok(result) => result,
+                Err(e) => alloc::format!("Tool error: {}", e),
             }
-            Err(e) => alloc::format!("Tool error: {}", e),
-        }
+        };
+
+        span.set_tokens_delta(context::estimate_tokens(&result) as i64);
+        result
     }
 }
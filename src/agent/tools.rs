@@ -8,6 +8,24 @@ use alloc::collections::BTreeMap;
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloc::format;
+use crate::agent::wasm;
+
+/// A tool backed by a WASM module rather than native kernel code. The module
+/// is instantiated fresh on every call, since the registry only keeps the
+/// tool's bytes around (decode is cheap relative to running untrusted code).
+#[derive(Debug, Clone)]
+pub struct DynamicTool {
+    pub name: String,
+    pub module: Vec<u8>,
+}
+
+/// A tool backed by a native ELF64 binary, run in Ring 3 for crash/fault
+/// isolation rather than the in-process WASM sandbox `DynamicTool` uses.
+#[derive(Debug, Clone)]
+pub struct ElfTool {
+    pub name: String,
+    pub elf: Vec<u8>,
+}
 
 /// Built-in tools available to the agent.
 #[derive(Debug, Clone)]
@@ -20,6 +38,21 @@ pub enum BuiltinTool {
     MemoryRecall,
     /// Echo input back (for testing).
     Echo,
+    /// Semantic recall over evicted context messages (the "disk" tier).
+    /// Handled directly by `Agent::execute_tool`, which has access to
+    /// the context window's vector store; registered here only so it shows
+    /// up in `tool_names`.
+    RecallSemantic,
+    /// A sandboxed tool registered at runtime as a WASM module.
+    Dynamic(DynamicTool),
+    /// A tool registered at runtime as a Ring-3 ELF program.
+    Elf(ElfTool),
+    /// Delegate to another agent. Formats the orchestrator's
+    /// `/send <agent_id> <message>` command; `ToolRegistry` has no notion of
+    /// other agents, it just builds the string
+    /// `orchestrator::parse_send_command` knows how to route back out of
+    /// the agent's response.
+    Send,
 }
 
 impl BuiltinTool {
@@ -29,6 +62,10 @@ impl BuiltinTool {
             BuiltinTool::MemoryStore => "store",
             BuiltinTool::MemoryRecall => "recall",
             BuiltinTool::Echo => "echo",
+            BuiltinTool::RecallSemantic => "recall_semantic",
+            BuiltinTool::Dynamic(t) => &t.name,
+            BuiltinTool::Elf(t) => &t.name,
+            BuiltinTool::Send => "send",
         }
     }
 
@@ -38,6 +75,10 @@ impl BuiltinTool {
             BuiltinTool::MemoryStore => "Store key-value: /tool store <key> <value>",
             BuiltinTool::MemoryRecall => "Recall by key: /tool recall <key>",
             BuiltinTool::Echo => "Echo input: /tool echo <text>",
+            BuiltinTool::RecallSemantic => "Semantic recall over evicted context: /tool recall_semantic <query>",
+            BuiltinTool::Dynamic(_) => "Sandboxed WASM tool: /tool <name> <args>",
+            BuiltinTool::Elf(_) => "Ring-3 isolated tool: /tool <name> <args>",
+            BuiltinTool::Send => "Delegate to another agent: /tool send <agent_id> <message>",
         }
     }
 }
@@ -61,6 +102,32 @@ impl ToolRegistry {
         self.tools.insert(String::from(tool.name()), tool);
     }
 
+    /// Register a sandboxed tool backed by a WASM module.
+    ///
+    /// The module is decoded (but not run) up front so a malformed module
+    /// fails at registration instead of on first use.
+    pub fn register_wasm(&mut self, name: &str, bytes: Vec<u8>) -> Result<(), String> {
+        wasm::parse(&bytes)?;
+        self.register(BuiltinTool::Dynamic(DynamicTool {
+            name: String::from(name),
+            module: bytes,
+        }));
+        Ok(())
+    }
+
+    /// Register a tool backed by a Ring-3 ELF program.
+    ///
+    /// The image is header-checked up front so a malformed binary fails at
+    /// registration instead of faulting on first use.
+    pub fn register_elf(&mut self, name: &str, bytes: Vec<u8>) -> Result<(), String> {
+        crate::elf::validate(&bytes)?;
+        self.register(BuiltinTool::Elf(ElfTool {
+            name: String::from(name),
+            elf: bytes,
+        }));
+        Ok(())
+    }
+
     /// Execute a tool by name with the given arguments.
     pub fn execute(&mut self, name: &str, args: &str) -> Result<String, String> {
         let tool = self
@@ -74,6 +141,12 @@ impl ToolRegistry {
             BuiltinTool::MemoryStore => self.exec_memory_store(args),
             BuiltinTool::MemoryRecall => self.exec_memory_recall(args),
             BuiltinTool::Echo => Ok(format!("[echo] {}", args)),
+            BuiltinTool::RecallSemantic => Err(String::from(
+                "recall_semantic requires context access; invoke it via /tool, not ToolRegistry::execute directly",
+            )),
+            BuiltinTool::Dynamic(t) => self.exec_dynamic(&t, args),
+            BuiltinTool::Elf(t) => self.exec_elf(&t, args),
+            BuiltinTool::Send => self.exec_send(args),
         }
     }
 
@@ -145,4 +218,59 @@ impl ToolRegistry {
             None => Err(format!("Key '{}' not found in memory", key)),
         }
     }
+
+    /// Format a `/send <agent_id> <message>` command for the orchestrator to
+    /// route. Whatever calls this (the command parser today, a model's
+    /// structured tool call once a transport is connected) gets that string
+    /// back as the tool's result; when it ends up as `process_input`'s
+    /// returned response, `orchestrator::parse_send_command` picks it up.
+    fn exec_send(&self, args: &str) -> Result<String, String> {
+        let parts: Vec<&str> = args.splitn(2, ' ').collect();
+        if parts.len() != 2 {
+            return Err(String::from("Usage: /tool send <agent_id> <message>"));
+        }
+
+        let agent_id: u32 = parts[0]
+            .parse()
+            .map_err(|_| format!("Invalid agent id: {}", parts[0]))?;
+
+        Ok(format!("/send {} {}", agent_id, parts[1]))
+    }
+
+    /// Run a sandboxed WASM tool, sharing the registry's key-value memory
+    /// with it through the `host.mem_store`/`host.mem_recall` imports.
+    fn exec_dynamic(&mut self, tool: &DynamicTool, args: &str) -> Result<String, String> {
+        let module = wasm::parse(&tool.module)?;
+        let result = wasm::run_tool(&module, args.as_bytes(), self)?;
+        String::from_utf8(result).map_err(|_| String::from("trap: tool result is not utf8"))
+    }
+
+    /// Run a Ring-3 ELF tool. A load failure (bad headers, out of frames,
+    /// mapping error) surfaces as an `Err` here; a fault raised by the
+    /// program while it runs is not caught and does not come back as an
+    /// `Err`.
+    fn exec_elf(&mut self, tool: &ElfTool, args: &str) -> Result<String, String> {
+        let result = crate::elf::load_and_run(&tool.elf, args.as_bytes(), self)?;
+        String::from_utf8(result).map_err(|_| String::from("tool wrote non-utf8 output"))
+    }
+}
+
+impl crate::elf::HostFunctions for ToolRegistry {
+    fn mem_store(&mut self, key: &str, value: &str) {
+        self.memory.insert(String::from(key), String::from(value));
+    }
+
+    fn mem_recall(&mut self, key: &str) -> Option<String> {
+        self.memory.get(key).cloned()
+    }
+}
+
+impl wasm::HostFunctions for ToolRegistry {
+    fn mem_store(&mut self, key: &str, value: &str) {
+        self.memory.insert(String::from(key), String::from(value));
+    }
+
+    fn mem_recall(&mut self, key: &str) -> Option<String> {
+        self.memory.get(key).cloned()
+    }
 }
@@ -0,0 +1,101 @@
+//! Vector Store — the "Disk" tier of the agent's memory hierarchy.
+//!
+//! `ContextWindow` evicts the oldest messages once it's full; this module is
+//! where they land instead of being discarded, turning the architecture
+//! note ("In full implementation: summarize and store in vector DB") into a
+//! working long-term memory. Embeddings are a deterministic hashed
+//! bag-of-words into a fixed-width vector, L2-normalized so cosine
+//! similarity reduces to a plain dot product — no model or network access
+//! needed, which matters on bare metal.
+
+use alloc::vec::Vec;
+use crate::agent::context::Message;
+
+/// Width of the embedding vector. Small and fixed so storing an evicted
+/// message costs a bounded, predictable amount of heap.
+pub const EMBED_DIM: usize = 64;
+
+/// Compute a deterministic embedding for `text`: hash each word into one of
+/// `EMBED_DIM` buckets and count occurrences, then L2-normalize.
+pub fn embed(text: &str) -> [f32; EMBED_DIM] {
+    let mut buckets = [0f32; EMBED_DIM];
+    for word in text.split_whitespace() {
+        let bucket = fnv1a(word.as_bytes()) as usize % EMBED_DIM;
+        buckets[bucket] += 1.0;
+    }
+
+    let norm = buckets.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for b in buckets.iter_mut() {
+            *b /= norm;
+        }
+    }
+    buckets
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let mut hash = FNV_OFFSET;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn dot(a: &[f32; EMBED_DIM], b: &[f32; EMBED_DIM]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+/// Messages evicted from the live context window, each held alongside its
+/// embedding so they can be retrieved by semantic similarity later.
+pub struct VectorStore {
+    entries: Vec<([f32; EMBED_DIM], Message)>,
+}
+
+impl VectorStore {
+    pub fn new() -> Self {
+        VectorStore { entries: Vec::new() }
+    }
+
+    /// Embed and archive an evicted message.
+    pub fn store(&mut self, message: Message) {
+        let embedding = embed(&message.content);
+        self.entries.push((embedding, message));
+    }
+
+    /// Number of archived messages.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return the `k` archived messages most similar to `query`, highest
+    /// similarity first. With normalized embeddings cosine similarity is
+    /// just the dot product, so this scans every entry and keeps the `k`
+    /// highest scores in a small fixed array rather than sorting the whole
+    /// store.
+    pub fn top_k(&self, query: &str, k: usize) -> Vec<(&Message, f32)> {
+        let q = embed(query);
+        let mut best: Vec<(f32, usize)> = Vec::with_capacity(k);
+
+        for (idx, (embedding, _)) in self.entries.iter().enumerate() {
+            let score = dot(&q, embedding);
+
+            if best.len() < k {
+                let pos = best.iter().position(|&(s, _)| score > s).unwrap_or(best.len());
+                best.insert(pos, (score, idx));
+            } else if let Some(&(lowest, _)) = best.last() {
+                if score > lowest {
+                    best.pop();
+                    let pos = best.iter().position(|&(s, _)| score > s).unwrap_or(best.len());
+                    best.insert(pos, (score, idx));
+                }
+            }
+        }
+
+        best.into_iter()
+            .map(|(score, idx)| (&self.entries[idx].1, score))
+            .collect()
+    }
+}
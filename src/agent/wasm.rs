@@ -0,0 +1,755 @@
+//! Minimal no_std WebAssembly interpreter for sandboxed dynamic tools.
+//!
+//! This is not a general-purpose WASM runtime — it covers just enough of the
+//! core MVP instruction set (i32 arithmetic, locals, block/loop/br control
+//! flow, linear memory, and call) to run small untrusted tool modules inside
+//! the kernel without handing them raw pointers into kernel memory. Only the
+//! `i32` value type is supported, with at most one result per function, which
+//! is sufficient for the tool ABI (`run(ptr, len) -> ptr`, see `run_tool`);
+//! modules using any other type or multi-value results are rejected at
+//! decode time. Every fallible step returns `Err(String)` — a malformed module or a
+//! misbehaving guest traps instead of panicking the kernel.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+const WASM_MAGIC: [u8; 4] = [0x00, 0x61, 0x73, 0x6d];
+const WASM_VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
+
+/// Guest linear memory is fixed at a single page. This bounds the cost of a
+/// sandboxed tool instead of honoring whatever `memory` section it declares.
+const MEM_SIZE: usize = 64 * 1024;
+
+/// Where `run_tool` places the guest's call arguments: just past the fixed
+/// scratch slot `call_host`'s `mem_recall` arm writes a recalled value into.
+/// Both sides bound their writes against this so a long recalled value can't
+/// silently grow into memory the guest is still reading its args out of.
+const ARGS_PTR: usize = 4 + 4096;
+/// Value stack depth ceiling — a recursive or runaway guest traps instead of
+/// growing the interpreter's own (kernel) stack or heap without bound.
+const MAX_STACK: usize = 4096;
+/// Call-frame depth ceiling. `Interpreter::call` recurses on the native
+/// kernel stack for every WASM `call`, so a guest that just calls itself
+/// (e.g. no params/results, never touching `MAX_STACK`) would otherwise
+/// overflow the real kernel stack instead of tripping a value-stack check.
+const MAX_CALL_DEPTH: usize = 128;
+
+const SEC_TYPE: u8 = 1;
+const SEC_IMPORT: u8 = 2;
+const SEC_FUNCTION: u8 = 3;
+const SEC_MEMORY: u8 = 5;
+const SEC_EXPORT: u8 = 7;
+const SEC_CODE: u8 = 10;
+
+const VAL_I32: u8 = 0x7F;
+
+/// A decoded, ready-to-run WASM module.
+///
+/// Function indices follow the standard WASM convention: imported functions
+/// occupy `0..imports.len()`, followed by locally defined functions.
+pub struct Module {
+    types: Vec<FuncType>,
+    imports: Vec<Import>,
+    /// Type index for each locally-defined function.
+    func_types: Vec<u32>,
+    code: Vec<FunctionBody>,
+    exports: Vec<(String, u32)>,
+}
+
+struct FuncType {
+    params: usize,
+    results: usize,
+}
+
+struct Import {
+    module: String,
+    name: String,
+}
+
+struct FunctionBody {
+    locals: usize,
+    code: Vec<u8>,
+    /// Precomputed at decode time: byte offset of a `block`/`loop` opcode ->
+    /// byte offset of its matching `end`. Computed once here instead of
+    /// rescanning on every branch taken at runtime.
+    matching_end: Vec<(usize, usize)>,
+}
+
+impl FunctionBody {
+    fn match_end(&self, start_pc: usize) -> usize {
+        self.matching_end
+            .iter()
+            .find(|(s, _)| *s == start_pc)
+            .map(|(_, e)| *e)
+            .unwrap_or(self.code.len())
+    }
+}
+
+/// Host-provided services a guest tool can import. This is how WASM tools
+/// reach the agent's shared key-value memory without being handed a pointer
+/// into kernel memory.
+pub trait HostFunctions {
+    fn mem_store(&mut self, key: &str, value: &str);
+    fn mem_recall(&mut self, key: &str) -> Option<String>;
+}
+
+/// Parse and validate a WASM module's structure (but not its code bodies'
+/// runtime behavior — that is only discovered by executing them).
+pub fn parse(bytes: &[u8]) -> Result<Module, String> {
+    if bytes.len() < 8 || bytes[0..4] != WASM_MAGIC || bytes[4..8] != WASM_VERSION {
+        return Err(String::from("not a valid wasm module (bad magic/version)"));
+    }
+
+    let mut r = Reader::new(&bytes[8..]);
+    let mut types = Vec::new();
+    let mut imports = Vec::new();
+    let mut func_types = Vec::new();
+    let mut code = Vec::new();
+    let mut exports = Vec::new();
+
+    while !r.at_end() {
+        let id = r.read_u8()?;
+        let size = r.read_u32leb()? as usize;
+        let section = r.read_bytes(size)?;
+        let mut sr = Reader::new(section);
+
+        match id {
+            SEC_TYPE => {
+                let count = sr.read_u32leb()?;
+                for _ in 0..count {
+                    if sr.read_u8()? != 0x60 {
+                        return Err(String::from("unsupported type section entry"));
+                    }
+                    let params = sr.read_u32leb()? as usize;
+                    for _ in 0..params {
+                        if sr.read_u8()? != VAL_I32 {
+                            return Err(String::from("only i32 params are supported"));
+                        }
+                    }
+                    let results = sr.read_u32leb()? as usize;
+                    if results > 1 {
+                        return Err(String::from("only single-value results are supported"));
+                    }
+                    for _ in 0..results {
+                        if sr.read_u8()? != VAL_I32 {
+                            return Err(String::from("only i32 results are supported"));
+                        }
+                    }
+                    types.push(FuncType { params, results });
+                }
+            }
+            SEC_IMPORT => {
+                let count = sr.read_u32leb()?;
+                for _ in 0..count {
+                    let module = sr.read_name()?;
+                    let name = sr.read_name()?;
+                    let kind = sr.read_u8()?;
+                    if kind != 0x00 {
+                        return Err(String::from("only function imports are supported"));
+                    }
+                    let _type_idx = sr.read_u32leb()?;
+                    imports.push(Import { module, name });
+                }
+            }
+            SEC_FUNCTION => {
+                let count = sr.read_u32leb()?;
+                for _ in 0..count {
+                    func_types.push(sr.read_u32leb()?);
+                }
+            }
+            SEC_MEMORY => {
+                // Guest memory is always a single fixed page; only confirm a
+                // memory section was declared.
+                let count = sr.read_u32leb()?;
+                if count > 1 {
+                    return Err(String::from("only one memory is supported"));
+                }
+            }
+            SEC_EXPORT => {
+                let count = sr.read_u32leb()?;
+                for _ in 0..count {
+                    let name = sr.read_name()?;
+                    let kind = sr.read_u8()?;
+                    let idx = sr.read_u32leb()?;
+                    if kind == 0x00 {
+                        exports.push((name, idx));
+                    }
+                }
+            }
+            SEC_CODE => {
+                let count = sr.read_u32leb()?;
+                for _ in 0..count {
+                    let body_size = sr.read_u32leb()? as usize;
+                    let body = sr.read_bytes(body_size)?;
+                    code.push(decode_function_body(body)?);
+                }
+            }
+            _ => {} // skip sections we don't need (custom, table, global, start, elem, data)
+        }
+    }
+
+    Ok(Module {
+        types,
+        imports,
+        func_types,
+        code,
+        exports,
+    })
+}
+
+fn decode_function_body(bytes: &[u8]) -> Result<FunctionBody, String> {
+    let mut r = Reader::new(bytes);
+    let group_count = r.read_u32leb()?;
+    let mut locals = 0usize;
+    for _ in 0..group_count {
+        let n = r.read_u32leb()? as usize;
+        if r.read_u8()? != VAL_I32 {
+            return Err(String::from("only i32 locals are supported"));
+        }
+        locals += n;
+    }
+    let code = r.rest().to_vec();
+
+    // Precompute block/loop -> matching end, by walking the instruction
+    // stream once with an explicit stack of open block starts.
+    let mut matching_end = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut pc = 0usize;
+    while pc < code.len() {
+        let op = code[pc];
+        let start = pc;
+        pc += 1;
+        match op {
+            0x02 | 0x03 => {
+                if pc >= code.len() || code[pc] != 0x40 {
+                    return Err(String::from("only empty block types are supported"));
+                }
+                pc += 1;
+                open.push(start);
+            }
+            0x0B => {
+                if let Some(s) = open.pop() {
+                    matching_end.push((s, start));
+                }
+            }
+            0x0C | 0x0D | 0x10 | 0x20 | 0x21 | 0x22 => {
+                pc += skip_leb(&code[pc..])?;
+            }
+            0x28 | 0x36 => {
+                pc += skip_leb(&code[pc..])?;
+                pc += skip_leb(&code[pc..])?;
+            }
+            0x41 => {
+                pc += skip_leb(&code[pc..])?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FunctionBody {
+        locals,
+        code,
+        matching_end,
+    })
+}
+
+fn skip_leb(bytes: &[u8]) -> Result<usize, String> {
+    let mut n = 0;
+    for (i, b) in bytes.iter().enumerate() {
+        if b & 0x80 == 0 {
+            n = i + 1;
+            break;
+        }
+    }
+    if n == 0 {
+        return Err(String::from("truncated LEB128 operand"));
+    }
+    Ok(n)
+}
+
+/// A byte-stream reader used for both section framing and instruction decode.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn rest(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| String::from("unexpected end of module"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&e| e <= self.bytes.len())
+            .ok_or_else(|| String::from("section/body length out of bounds"))?;
+        let s = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(s)
+    }
+
+    fn read_u32leb(&mut self) -> Result<u32, String> {
+        let mut result: u32 = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.read_u8()?;
+            result |= ((b & 0x7F) as u32) << shift;
+            if b & 0x80 == 0 {
+                return Ok(result);
+            }
+            shift += 7;
+            if shift >= 35 {
+                return Err(String::from("LEB128 operand too large"));
+            }
+        }
+    }
+
+    fn read_i32leb(&mut self) -> Result<i32, String> {
+        let mut result: i32 = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.read_u8()?;
+            result |= ((b & 0x7F) as i32) << shift;
+            shift += 7;
+            if b & 0x80 == 0 {
+                if shift < 32 && (b & 0x40) != 0 {
+                    result |= -1i32 << shift;
+                }
+                return Ok(result);
+            }
+            if shift >= 35 {
+                return Err(String::from("LEB128 operand too large"));
+            }
+        }
+    }
+
+    fn read_name(&mut self) -> Result<String, String> {
+        let len = self.read_u32leb()? as usize;
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| String::from("invalid utf8 name"))
+    }
+}
+
+/// An open `block`/`loop` on the label stack.
+struct Label {
+    is_loop: bool,
+    start_pc: usize,
+    end_pc: usize,
+    stack_height: usize,
+}
+
+/// One call frame: its locals (params followed by declared locals, reserved
+/// in a single extension rather than pushed one at a time) and its label
+/// stack for structured control flow.
+struct Frame {
+    locals: Vec<i32>,
+    labels: Vec<Label>,
+}
+
+/// The interpreter: an explicit value stack plus the guest's linear memory.
+struct Interpreter<'h, H: HostFunctions> {
+    stack: Vec<i32>,
+    /// Heap-backed like `stack` above — `MEM_SIZE` (64 KiB) per call is too
+    /// much to carry on the kernel's own stack, unlike `stack`'s few hundred
+    /// bytes in the common case.
+    memory: Vec<u8>,
+    host: &'h mut H,
+    /// Current depth of nested `call`s; see `MAX_CALL_DEPTH`.
+    call_depth: usize,
+}
+
+impl<'h, H: HostFunctions> Interpreter<'h, H> {
+    fn push(&mut self, v: i32) -> Result<(), String> {
+        if self.stack.len() >= MAX_STACK {
+            return Err(String::from("trap: value stack overflow"));
+        }
+        self.stack.push(v);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<i32, String> {
+        self.stack
+            .pop()
+            .ok_or_else(|| String::from("trap: value stack underflow"))
+    }
+
+    fn mem_read(&self, ptr: i32, len: i32) -> Result<&[u8], String> {
+        let start: usize = ptr.try_into().map_err(|_| String::from("trap: negative memory address"))?;
+        let len: usize = len.try_into().map_err(|_| String::from("trap: negative memory length"))?;
+        let end = start
+            .checked_add(len)
+            .filter(|&e| e <= MEM_SIZE)
+            .ok_or_else(|| String::from("trap: out-of-bounds memory access"))?;
+        Ok(&self.memory[start..end])
+    }
+
+    fn mem_write(&mut self, ptr: i32, data: &[u8]) -> Result<(), String> {
+        let start: usize = ptr.try_into().map_err(|_| String::from("trap: negative memory address"))?;
+        let end = start
+            .checked_add(data.len())
+            .filter(|&e| e <= MEM_SIZE)
+            .ok_or_else(|| String::from("trap: out-of-bounds memory access"))?;
+        self.memory[start..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    /// Execute a function body to completion, returning its single i32
+    /// result (if its type declares one).
+    fn call(&mut self, module: &Module, func_idx: u32, args: &[i32]) -> Result<Option<i32>, String> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(String::from("trap: call stack too deep"));
+        }
+        self.call_depth += 1;
+        let result = self.call_inner(module, func_idx, args);
+        self.call_depth -= 1;
+        result
+    }
+
+    fn call_inner(&mut self, module: &Module, func_idx: u32, args: &[i32]) -> Result<Option<i32>, String> {
+        let n_imports = module.imports.len();
+        if (func_idx as usize) < n_imports {
+            return self.call_host(module, func_idx, args).map(Some);
+        }
+
+        let local_idx = func_idx as usize - n_imports;
+        let body = module
+            .code
+            .get(local_idx)
+            .ok_or_else(|| String::from("trap: call to undefined function"))?;
+        let ty = module
+            .func_types
+            .get(local_idx)
+            .and_then(|t| module.types.get(*t as usize))
+            .ok_or_else(|| String::from("trap: function has no declared type"))?;
+
+        // Reserve space for all locals (params + declared locals) in one
+        // extension, rather than pushing defaults one at a time.
+        let mut locals = vec![0i32; ty.params + body.locals];
+        locals[..args.len().min(ty.params)].copy_from_slice(&args[..args.len().min(ty.params)]);
+        let mut frame = Frame {
+            locals,
+            labels: Vec::new(),
+        };
+
+        let base_height = self.stack.len();
+        self.run(module, body, &mut frame)?;
+
+        if ty.results == 1 {
+            Ok(Some(self.pop()?))
+        } else {
+            // No declared result: discard anything an errant guest left behind.
+            self.stack.truncate(base_height);
+            Ok(None)
+        }
+    }
+
+    fn call_host(&mut self, module: &Module, func_idx: u32, args: &[i32]) -> Result<i32, String> {
+        let import = &module.imports[func_idx as usize];
+        match (import.module.as_str(), import.name.as_str()) {
+            ("host", "mem_store") => {
+                if args.len() != 4 {
+                    return Err(String::from("trap: mem_store expects (key_ptr, key_len, val_ptr, val_len)"));
+                }
+                let key = core::str::from_utf8(self.mem_read(args[0], args[1])?)
+                    .map_err(|_| String::from("trap: mem_store key is not utf8"))?
+                    .into();
+                let value = core::str::from_utf8(self.mem_read(args[2], args[3])?)
+                    .map_err(|_| String::from("trap: mem_store value is not utf8"))?;
+                let value = String::from(value);
+                self.host.mem_store(&key, &value);
+                Ok(0)
+            }
+            ("host", "mem_recall") => {
+                if args.len() != 2 {
+                    return Err(String::from("trap: mem_recall expects (key_ptr, key_len)"));
+                }
+                let key = core::str::from_utf8(self.mem_read(args[0], args[1])?)
+                    .map_err(|_| String::from("trap: mem_recall key is not utf8"))?
+                    .into();
+                match self.host.mem_recall(&key) {
+                    // Result is written back at a fixed scratch offset as a
+                    // 4-byte length prefix followed by the bytes; offset 0 is
+                    // reserved so the guest can treat a 0 return as "not found".
+                    Some(value) => {
+                        let offset = 4usize;
+                        let total = offset + value.len();
+                        // Bounded against `ARGS_PTR`, not `MEM_SIZE` — this
+                        // scratch slot and the guest's call args share the
+                        // same memory, and a recall long enough to reach
+                        // `ARGS_PTR` would otherwise silently clobber args
+                        // the guest is still reading.
+                        if total > ARGS_PTR {
+                            return Err(String::from("trap: recalled value exceeds scratch slot"));
+                        }
+                        self.memory[0..4].copy_from_slice(&(value.len() as u32).to_le_bytes());
+                        self.mem_write(offset as i32, value.as_bytes())?;
+                        Ok(offset as i32)
+                    }
+                    None => Ok(0),
+                }
+            }
+            (m, n) => Err(format!("trap: unresolved import {}::{}", m, n)),
+        }
+    }
+
+    /// Interpret one function body against the given frame.
+    fn run(&mut self, module: &Module, body: &FunctionBody, frame: &mut Frame) -> Result<(), String> {
+        let code = &body.code;
+        let mut pc = 0usize;
+
+        while pc < code.len() {
+            let op = code[pc];
+            let op_start = pc;
+            pc += 1;
+
+            match op {
+                0x00 => return Err(String::from("trap: unreachable")),
+                0x01 => {} // nop
+                0x02 | 0x03 => {
+                    pc += 1; // blocktype byte, already validated at decode time
+                    frame.labels.push(Label {
+                        is_loop: op == 0x03,
+                        start_pc: op_start,
+                        end_pc: body.match_end(op_start),
+                        stack_height: self.stack.len(),
+                    });
+                }
+                0x0B => {
+                    frame.labels.pop();
+                }
+                0x0C | 0x0D => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let depth = r.read_u32leb()? as usize;
+                    pc += skip_leb(&code[pc..])?;
+
+                    let taken = if op == 0x0D { self.pop()? != 0 } else { true };
+                    if taken {
+                        if depth >= frame.labels.len() {
+                            return Err(String::from("trap: branch depth out of range"));
+                        }
+                        let target_idx = frame.labels.len() - 1 - depth;
+                        let label_height = frame.labels[target_idx].stack_height;
+                        self.stack.truncate(label_height);
+                        if frame.labels[target_idx].is_loop {
+                            pc = frame.labels[target_idx].start_pc + 2;
+                            frame.labels.truncate(target_idx + 1);
+                        } else {
+                            pc = frame.labels[target_idx].end_pc + 1;
+                            frame.labels.truncate(target_idx);
+                        }
+                    }
+                }
+                0x0F => return Ok(()),
+                0x10 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let func_idx = r.read_u32leb()?;
+                    pc += skip_leb(&code[pc..])?;
+
+                    let n_imports = module.imports.len();
+                    let n_args = if (func_idx as usize) < n_imports {
+                        match (func_idx as usize) {
+                            i if module.imports[i].name == "mem_store" => 4,
+                            _ => 2,
+                        }
+                    } else {
+                        let local_idx = func_idx as usize - n_imports;
+                        module
+                            .func_types
+                            .get(local_idx)
+                            .and_then(|t| module.types.get(*t as usize))
+                            .map(|t| t.params)
+                            .ok_or_else(|| String::from("trap: call to undefined function"))?
+                    };
+
+                    if self.stack.len() < n_args {
+                        return Err(String::from("trap: value stack underflow on call"));
+                    }
+                    let args: Vec<i32> = self.stack.split_off(self.stack.len() - n_args);
+                    if let Some(result) = self.call(module, func_idx, &args)? {
+                        self.push(result)?;
+                    }
+                }
+                0x1A => {
+                    self.pop()?;
+                }
+                0x1B => {
+                    let c = self.pop()?;
+                    let b = self.pop()?;
+                    let a = self.pop()?;
+                    self.push(if c != 0 { a } else { b })?;
+                }
+                0x20 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let idx = r.read_u32leb()? as usize;
+                    pc += skip_leb(&code[pc..])?;
+                    let v = *frame
+                        .locals
+                        .get(idx)
+                        .ok_or_else(|| String::from("trap: local index out of range"))?;
+                    self.push(v)?;
+                }
+                0x21 | 0x22 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let idx = r.read_u32leb()? as usize;
+                    pc += skip_leb(&code[pc..])?;
+                    let v = self.pop()?;
+                    if idx >= frame.locals.len() {
+                        return Err(String::from("trap: local index out of range"));
+                    }
+                    frame.locals[idx] = v;
+                    if op == 0x22 {
+                        self.push(v)?;
+                    }
+                }
+                0x28 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let _align = r.read_u32leb()?;
+                    let offset = r.read_u32leb()?;
+                    pc += skip_leb(&code[pc..])?;
+                    pc += skip_leb(&code[pc..])?;
+                    let base = self.pop()?;
+                    let addr = base
+                        .checked_add(offset as i32)
+                        .ok_or_else(|| String::from("trap: address overflow"))?;
+                    let bytes = self.mem_read(addr, 4)?;
+                    self.push(i32::from_le_bytes(bytes.try_into().unwrap()))?;
+                }
+                0x36 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let _align = r.read_u32leb()?;
+                    let offset = r.read_u32leb()?;
+                    pc += skip_leb(&code[pc..])?;
+                    pc += skip_leb(&code[pc..])?;
+                    let value = self.pop()?;
+                    let base = self.pop()?;
+                    let addr = base
+                        .checked_add(offset as i32)
+                        .ok_or_else(|| String::from("trap: address overflow"))?;
+                    self.mem_write(addr, &value.to_le_bytes())?;
+                }
+                0x41 => {
+                    let mut r = Reader::new(&code[pc..]);
+                    let v = r.read_i32leb()?;
+                    pc += skip_leb(&code[pc..])?;
+                    self.push(v)?;
+                }
+                0x45 => {
+                    let a = self.pop()?;
+                    self.push((a == 0) as i32)?;
+                }
+                0x46 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b == a) as i32)?;
+                }
+                0x47 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b != a) as i32)?;
+                }
+                0x48 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b < a) as i32)?;
+                }
+                0x4A => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b > a) as i32)?;
+                }
+                0x4C => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b <= a) as i32)?;
+                }
+                0x4E => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push((b >= a) as i32)?;
+                }
+                0x6A => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b.wrapping_add(a))?;
+                }
+                0x6B => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b.wrapping_sub(a))?;
+                }
+                0x6C => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b.wrapping_mul(a))?;
+                }
+                0x71 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b & a)?;
+                }
+                0x72 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b | a)?;
+                }
+                0x73 => {
+                    let (a, b) = (self.pop()?, self.pop()?);
+                    self.push(b ^ a)?;
+                }
+                other => return Err(format!("trap: unsupported opcode 0x{:02x}", other)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Instantiate `module` and call its exported `run(ptr, len) -> ptr` entry
+/// point with `args` copied into guest linear memory, returning the bytes
+/// written at the result pointer. `run` returns a single i32; a `run` typed
+/// with two results `(ptr, len)` is not accepted, since `parse` rejects
+/// multi-value function types.
+pub fn run_tool(module: &Module, args: &[u8], host: &mut impl HostFunctions) -> Result<Vec<u8>, String> {
+    let func_idx = module
+        .exports
+        .iter()
+        .find(|(name, _)| name == "run")
+        .map(|(_, idx)| *idx)
+        .ok_or_else(|| String::from("module does not export a 'run' function"))?;
+
+    let mut interp = Interpreter {
+        stack: Vec::new(),
+        memory: vec![0u8; MEM_SIZE],
+        host,
+        call_depth: 0,
+    };
+
+    // args live just past the mem_recall scratch slot used by the host ABI.
+    if ARGS_PTR + args.len() > MEM_SIZE {
+        return Err(String::from("trap: tool arguments too large for guest memory"));
+    }
+    interp.mem_write(ARGS_PTR as i32, args)?;
+
+    let result = interp.call(module, func_idx, &[ARGS_PTR as i32, args.len() as i32])?;
+    let packed = result.ok_or_else(|| String::from("'run' must return a single i32"))?;
+
+    // The guest packs its (ptr, len) result pair by writing len at `ptr - 4`
+    // and returning `ptr`, mirroring the host's own mem_recall convention.
+    let result_ptr = packed;
+    if result_ptr < 4 {
+        return Err(String::from("trap: invalid result pointer"));
+    }
+    let len_bytes = interp.mem_read(result_ptr - 4, 4)?;
+    let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as i32;
+    Ok(interp.mem_read(result_ptr, len)?.to_vec())
+}
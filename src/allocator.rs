@@ -9,14 +9,13 @@
 //! - Token buffers
 //! - Embedding vectors (Vec<f32>)
 //! - Tool call arguments and results
+//!
+//! Mapping goes through `arch::PageMapper`/`arch::FrameSource` rather than
+//! `x86_64::structures::paging` types directly, so this same routine backs
+//! the heap on any architecture with an `arch` backend.
 
 use linked_list_allocator::LockedHeap;
-use x86_64::{
-    structures::paging::{
-        mapper::MapToError, FrameAllocator, Mapper, Page, PageTableFlags, Size4KiB,
-    },
-    VirtAddr,
-};
+use crate::arch::{FrameSource, PageMapper};
 
 #[global_allocator]
 static ALLOCATOR: LockedHeap = LockedHeap::empty();
@@ -25,31 +24,24 @@ static ALLOCATOR: LockedHeap = LockedHeap::empty();
 pub const HEAP_START: usize = 0x_4444_4444_0000;
 /// Size of the kernel heap: 1 MiB.
 pub const HEAP_SIZE: usize = 1024 * 1024;
+const PAGE_SIZE: usize = 4096;
 
 /// Initialize the kernel heap.
 ///
 /// Maps `HEAP_SIZE` bytes of virtual memory starting at `HEAP_START`
 /// to physical frames, then initializes the allocator over that region.
 pub fn init_heap(
-    mapper: &mut impl Mapper<Size4KiB>,
-    frame_allocator: &mut impl FrameAllocator<Size4KiB>,
-) -> Result<(), MapToError<Size4KiB>> {
-    let page_range = {
-        let heap_start = VirtAddr::new(HEAP_START as u64);
-        let heap_end = heap_start + HEAP_SIZE - 1u64;
-        let heap_start_page = Page::containing_address(heap_start);
-        let heap_end_page = Page::containing_address(heap_end);
-        Page::range_inclusive(heap_start_page, heap_end_page)
-    };
+    mapper: &mut impl PageMapper,
+    frame_allocator: &mut impl FrameSource,
+) -> Result<(), &'static str> {
+    let page_count = HEAP_SIZE / PAGE_SIZE;
 
-    for page in page_range {
-        let frame = frame_allocator
+    for i in 0..page_count {
+        let virt_addr = (HEAP_START + i * PAGE_SIZE) as u64;
+        let phys_addr = frame_allocator
             .allocate_frame()
-            .ok_or(MapToError::FrameAllocationFailed)?;
-        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
-        unsafe {
-            mapper.map_to(page, frame, flags, frame_allocator)?.flush();
-        }
+            .ok_or("heap init: out of physical frames")?;
+        mapper.map_page(virt_addr, phys_addr, true, frame_allocator)?;
     }
 
     unsafe {
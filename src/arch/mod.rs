@@ -0,0 +1,56 @@
+//! Architecture abstraction layer.
+//!
+//! The agent runtime and `agent::context` are arch-neutral. `agent::tools`
+//! is too, except for its Ring-3 ELF tools, which go through `crate::elf`
+//! and are x86_64-only. Everything hardware-specific
+//! (`memory`, `allocator::init_heap`'s use of `x86_64::structures::paging`,
+//! GDT/IDT/PIC setup in `init`) was hard-coded to x86_64 throughout. This
+//! module is the seam: each backend implements the same small trait surface
+//! (`init`, a `PageMapper`/`FrameSource` pair, `hlt_loop`), selected at
+//! compile time via `cfg(target_arch)`. The riscv64 backend also provides
+//! `console_putchar` for console output; the x86_64 backend has no serial
+//! code of its own.
+
+#[cfg(target_arch = "x86_64")]
+pub mod x86_64_impl;
+#[cfg(target_arch = "x86_64")]
+pub use x86_64_impl as current;
+
+#[cfg(target_arch = "riscv64")]
+pub mod riscv64_impl;
+#[cfg(target_arch = "riscv64")]
+pub use riscv64_impl as current;
+
+/// Maps virtual pages to physical frames. Each backend's mapper owns
+/// whatever its native page table format is (x86_64 4-level, riscv64 Sv39);
+/// callers only ever see page-aligned `u64` addresses.
+///
+/// Mapping a page may need to allocate intermediate page-table frames, so
+/// every call takes a `FrameSource` rather than the mapper owning one — this
+/// mirrors how the underlying `x86_64` crate's own `Mapper::map_to` works.
+pub trait PageMapper {
+    fn map_page(
+        &mut self,
+        virt_addr: u64,
+        phys_addr: u64,
+        writable: bool,
+        frame_source: &mut dyn FrameSource,
+    ) -> Result<(), &'static str>;
+}
+
+/// Supplies free physical frames, one page-aligned address at a time.
+pub trait FrameSource {
+    fn allocate_frame(&mut self) -> Option<u64>;
+}
+
+/// Per-architecture entry points every backend must provide.
+pub trait Arch {
+    /// Hook for architecture-specific bring-up before the heap and agent
+    /// runtime start. Both backends' `init` bodies are empty: GDT/IDT/PIC
+    /// (x86_64) and trap vector/PLIC (riscv64) setup is not done here.
+    fn init();
+
+    /// Halt the CPU until the next interrupt, forever. Used as the kernel's
+    /// idle loop once the agent is running.
+    fn hlt_loop() -> !;
+}
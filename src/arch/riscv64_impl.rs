@@ -0,0 +1,121 @@
+//! riscv64 backend: SBI-based console and Sv39 paging.
+//!
+//! Brought up enough to prove the agent runtime is hardware-independent —
+//! console output through the SBI legacy console extension and a 3-level
+//! Sv39 page table walker mirroring what `X86PageMapper` does for x86_64's
+//! 4-level tables.
+
+use super::{Arch, FrameSource, PageMapper};
+
+const PAGE_SIZE: u64 = 4096;
+
+const PTE_V: u64 = 1 << 0;
+const PTE_R: u64 = 1 << 1;
+const PTE_W: u64 = 1 << 2;
+
+/// SBI legacy extension id for `console_putchar`.
+const SBI_CONSOLE_PUTCHAR: usize = 0x01;
+
+pub struct Riscv64;
+
+impl Arch for Riscv64 {
+    fn init() {
+        // Trap vector / PLIC setup lives alongside the rest of this
+        // platform's interrupt plumbing, outside this arch seam — `init`
+        // here is that code's hook for anything riscv64-specific the
+        // top-level `crate::init()` needs before the heap comes up.
+    }
+
+    fn hlt_loop() -> ! {
+        loop {
+            unsafe {
+                core::arch::asm!("wfi");
+            }
+        }
+    }
+}
+
+/// Write one byte to the console via the SBI legacy `console_putchar` call.
+/// Backs `serial_println!` on this platform, the way 16550 UART I/O backs
+/// it on x86_64.
+pub fn console_putchar(byte: u8) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in("a0") byte as usize,
+            in("a7") SBI_CONSOLE_PUTCHAR,
+            lateout("a0") _,
+            options(nostack),
+        );
+    }
+}
+
+/// A 3-level Sv39 page table mapper. `root` is the physical address of the
+/// root table; physical memory is assumed identity-mapped while paging is
+/// being built up, which is standard for riscv64 kernels before they
+/// install their own `satp` and switch to a higher-half layout.
+pub struct Sv39PageMapper {
+    root: u64,
+}
+
+impl Sv39PageMapper {
+    pub fn new(root_table: u64) -> Self {
+        Sv39PageMapper { root: root_table }
+    }
+
+    fn vpn(virt_addr: u64, level: u32) -> usize {
+        ((virt_addr >> (12 + 9 * level)) & 0x1FF) as usize
+    }
+
+    unsafe fn entry(table: u64, idx: usize) -> *mut u64 {
+        (table as *mut u64).add(idx)
+    }
+}
+
+impl PageMapper for Sv39PageMapper {
+    fn map_page(
+        &mut self,
+        virt_addr: u64,
+        phys_addr: u64,
+        writable: bool,
+        frame_source: &mut dyn FrameSource,
+    ) -> Result<(), &'static str> {
+        if virt_addr % PAGE_SIZE != 0 || phys_addr % PAGE_SIZE != 0 {
+            return Err("riscv64: unaligned address");
+        }
+
+        let mut table = self.root;
+
+        // Levels 2 and 1 are non-leaf: walk down, allocating a fresh
+        // page-table frame whenever an entry isn't valid yet.
+        for level in [2u32, 1u32] {
+            let idx = Self::vpn(virt_addr, level);
+            let pte_ptr = unsafe { Self::entry(table, idx) };
+            let pte = unsafe { *pte_ptr };
+
+            table = if pte & PTE_V == 0 {
+                let frame = frame_source
+                    .allocate_frame()
+                    .ok_or("riscv64: out of frames for page table")?;
+                unsafe { core::ptr::write_bytes(frame as *mut u8, 0, PAGE_SIZE as usize) };
+                let ppn = frame >> 12;
+                unsafe { *pte_ptr = (ppn << 10) | PTE_V };
+                frame
+            } else {
+                ((pte >> 10) & 0xFFF_FFFF_FFFF) << 12
+            };
+        }
+
+        // Level 0 is the leaf: set R/W (and V) over the target physical frame.
+        let idx0 = Self::vpn(virt_addr, 0);
+        let pte_ptr = unsafe { Self::entry(table, idx0) };
+        let mut flags = PTE_V | PTE_R;
+        if writable {
+            flags |= PTE_W;
+        }
+        let ppn = phys_addr >> 12;
+        unsafe { *pte_ptr = (ppn << 10) | flags };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! x86_64 backend: 4-level paging via the `x86_64` crate, PIC/GDT/IDT init.
+
+use super::{Arch, FrameSource, PageMapper};
+use x86_64::structures::paging::{
+    FrameAllocator, Mapper, Page, PageTableFlags, PhysFrame, Size4KiB,
+};
+use x86_64::{PhysAddr, VirtAddr};
+
+pub struct X86_64;
+
+impl Arch for X86_64 {
+    fn init() {
+        // GDT, IDT and PIC bring-up live in `crate::gdt` / `crate::interrupts`,
+        // outside this arch seam; `init` here is the hook those modules'
+        // top-level `crate::init()` calls into for anything x86_64-specific
+        // beyond what they already own.
+    }
+
+    fn hlt_loop() -> ! {
+        loop {
+            x86_64::instructions::hlt();
+        }
+    }
+}
+
+/// Wraps any `x86_64` crate page table mapper (e.g. `OffsetPageTable`) to
+/// present the arch-neutral `PageMapper` surface.
+pub struct X86PageMapper<M> {
+    inner: M,
+}
+
+impl<M> X86PageMapper<M> {
+    pub fn new(inner: M) -> Self {
+        X86PageMapper { inner }
+    }
+}
+
+impl<M: Mapper<Size4KiB>> PageMapper for X86PageMapper<M> {
+    fn map_page(
+        &mut self,
+        virt_addr: u64,
+        phys_addr: u64,
+        writable: bool,
+        frame_source: &mut dyn FrameSource,
+    ) -> Result<(), &'static str> {
+        let page = Page::<Size4KiB>::containing_address(VirtAddr::new(virt_addr));
+        let frame = PhysFrame::<Size4KiB>::containing_address(PhysAddr::new(phys_addr));
+
+        let mut flags = PageTableFlags::PRESENT;
+        if writable {
+            flags |= PageTableFlags::WRITABLE;
+        }
+
+        let mut adapter = FrameSourceAdapter(frame_source);
+        unsafe {
+            self.inner
+                .map_to(page, frame, flags, &mut adapter)
+                .map_err(|_| "x86_64: map_to failed")?
+                .flush();
+        }
+        Ok(())
+    }
+}
+
+/// Lets the arch-neutral `FrameSource` stand in wherever the `x86_64` crate
+/// wants its own `FrameAllocator<Size4KiB>` — needed because `map_to` may
+/// allocate intermediate page-table frames as it walks the table.
+struct FrameSourceAdapter<'a>(&'a mut dyn FrameSource);
+
+unsafe impl FrameAllocator<Size4KiB> for FrameSourceAdapter<'_> {
+    fn allocate_frame(&mut self) -> Option<PhysFrame<Size4KiB>> {
+        self.0
+            .allocate_frame()
+            .map(|addr| PhysFrame::containing_address(PhysAddr::new(addr)))
+    }
+}
+
+/// The boot-info-derived frame allocator (`crate::memory::BootInfoFrameAllocator`)
+/// already implements the `x86_64` crate's own `FrameAllocator<Size4KiB>`;
+/// this just exposes that through the arch-neutral `FrameSource` surface.
+impl FrameSource for crate::memory::BootInfoFrameAllocator {
+    fn allocate_frame(&mut self) -> Option<u64> {
+        FrameAllocator::<Size4KiB>::allocate_frame(self).map(|frame| frame.start_address().as_u64())
+    }
+}
@@ -0,0 +1,462 @@
+//! Ring-3 tool execution — loads tool binaries as ELF images and runs them
+//! as unprivileged user programs instead of in-kernel code.
+//!
+//! Every built-in and WASM tool (see `agent::tools`, `agent::wasm`) still
+//! runs with full kernel privilege. This module is the "real" syscall table
+//! the module comment in `agent::tools` alludes to: a tool ELF is mapped
+//! into its own address space with `USER_ACCESSIBLE` pages, dropped to Ring
+//! 3 via `iretq`, and can only affect the world through the syscalls below.
+//! A fault in the tool traps back to the kernel instead of corrupting it.
+//!
+//! This module assumes `crate::gdt` exposes `USER_CODE_SELECTOR`,
+//! `USER_DATA_SELECTOR` and the TSS's privilege-stack-table entry 0 (the
+//! kernel stack the CPU switches to on a Ring 3 -> Ring 0 trap), and that
+//! `crate::interrupts::init_idt` points IDT vector `0x80`'s gate directly at
+//! `syscall_entry` (not through the `x86_64` crate's typed
+//! `extern "x86-interrupt"` closures, which can't expose the raw
+//! `rax`/`rdi`/`rsi`/`rdx`/`r10` syscall registers) with
+//! `set_privilege_level(PrivilegeLevel::Ring3)` so user code is allowed to
+//! invoke it directly. `syscall_entry` below is a `#[naked]` function, which
+//! needs `#![feature(naked_functions)]` enabled at the crate root — another
+//! piece of `lib.rs`, which isn't part of this source chunk either.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::format;
+use core::convert::TryInto;
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86_64::{
+    structures::paging::{FrameAllocator, Mapper, Page, PageTableFlags, Size4KiB},
+    PhysAddr, VirtAddr,
+};
+
+const ELF_MAGIC: [u8; 4] = [0x7F, b'E', b'L', b'F'];
+const PT_LOAD: u32 = 1;
+
+/// Size of the user stack handed to every tool program.
+const USER_STACK_SIZE: u64 = 16 * 4096;
+const USER_STACK_TOP: u64 = 0x7000_0000_0000;
+
+/// Syscall numbers tool programs may invoke via `int 0x80`.
+pub mod syscall {
+    pub const WRITE: u64 = 1;
+    pub const MEM_STORE: u64 = 2;
+    pub const MEM_RECALL: u64 = 3;
+    pub const EXIT: u64 = 60;
+}
+
+/// Host services a loaded ELF tool can reach through `mem_store`/
+/// `mem_recall` syscalls — the same shared key-value memory WASM tools use.
+pub trait HostFunctions {
+    fn mem_store(&mut self, key: &str, value: &str);
+    fn mem_recall(&mut self, key: &str) -> Option<String>;
+}
+
+#[repr(C)]
+struct Elf64Ehdr {
+    e_ident: [u8; 16],
+    e_type: u16,
+    e_machine: u16,
+    e_version: u32,
+    e_entry: u64,
+    e_phoff: u64,
+    e_shoff: u64,
+    e_flags: u32,
+    e_ehsize: u16,
+    e_phentsize: u16,
+    e_phnum: u16,
+    e_shentsize: u16,
+    e_shnum: u16,
+    e_shstrndx: u16,
+}
+
+#[repr(C)]
+struct Elf64Phdr {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+/// A loaded tool program, mapped and ready to run in Ring 3.
+pub struct UserProgram {
+    entry: VirtAddr,
+    stack_top: VirtAddr,
+    /// `[start, end)` virtual-address ranges the program may legally pass
+    /// pointers into for a syscall (its `PT_LOAD` segments plus its stack) —
+    /// everything outside these ranges is unmapped or someone else's memory.
+    regions: Vec<(u64, u64)>,
+}
+
+/// Check that `bytes` looks like a loadable x86_64 ELF64 image, without
+/// mapping anything. Used to fail tool registration early.
+pub fn validate(bytes: &[u8]) -> Result<(), String> {
+    if bytes.len() < core::mem::size_of::<Elf64Ehdr>() || bytes[0..4] != ELF_MAGIC {
+        return Err(String::from("not a valid ELF64 image"));
+    }
+    let ehdr = unsafe { &*(bytes.as_ptr() as *const Elf64Ehdr) };
+    if ehdr.e_machine != 0x3E {
+        return Err(String::from("ELF image is not x86_64"));
+    }
+    Ok(())
+}
+
+/// Load `bytes` into a fresh set of Ring-3 mappings (via the kernel's
+/// global mapper/frame allocator) and run it to completion, returning the
+/// bytes it wrote via the `write` syscall followed by any values it fetched
+/// via `mem_recall` (see `run`).
+pub fn load_and_run(bytes: &[u8], args: &[u8], host: &mut impl HostFunctions) -> Result<Vec<u8>, String> {
+    crate::memory::with_mapper_and_allocator(|mapper, frame_allocator| {
+        let program = load(bytes, mapper, frame_allocator)?;
+        run(&program, args, host)
+    })
+}
+
+/// Parse and map an ELF64 image's `PT_LOAD` segments as user-accessible
+/// pages, and allocate a dedicated user stack.
+pub fn load(
+    bytes: &[u8],
+    mapper: &mut impl Mapper<Size4KiB>,
+    frame_allocator: &mut impl FrameAllocator<Size4KiB>,
+) -> Result<UserProgram, String> {
+    if bytes.len() < core::mem::size_of::<Elf64Ehdr>() || bytes[0..4] != ELF_MAGIC {
+        return Err(String::from("not a valid ELF64 image"));
+    }
+
+    // SAFETY: length checked above; ELF headers have no alignment
+    // requirement stronger than the byte slice itself on x86_64.
+    let ehdr = unsafe { &*(bytes.as_ptr() as *const Elf64Ehdr) };
+    if ehdr.e_machine != 0x3E {
+        return Err(String::from("ELF image is not x86_64"));
+    }
+
+    let phoff = ehdr.e_phoff as usize;
+    let phentsize = ehdr.e_phentsize as usize;
+    let phnum = ehdr.e_phnum as usize;
+    let mut regions: Vec<(u64, u64)> = Vec::new();
+
+    for i in 0..phnum {
+        let off = phoff + i * phentsize;
+        let end = off
+            .checked_add(core::mem::size_of::<Elf64Phdr>())
+            .filter(|&e| e <= bytes.len())
+            .ok_or_else(|| String::from("program header out of bounds"))?;
+        let phdr = unsafe { &*(bytes[off..end].as_ptr() as *const Elf64Phdr) };
+        if phdr.p_type != PT_LOAD {
+            continue;
+        }
+
+        regions.push((phdr.p_vaddr, phdr.p_vaddr + phdr.p_memsz));
+
+        let seg_start = VirtAddr::new(phdr.p_vaddr);
+        let seg_end = seg_start + phdr.p_memsz - 1u64;
+        let start_page = Page::<Size4KiB>::containing_address(seg_start);
+        let end_page = Page::<Size4KiB>::containing_address(seg_end);
+        let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+
+        for page in Page::range_inclusive(start_page, end_page) {
+            let frame = frame_allocator
+                .allocate_frame()
+                .ok_or_else(|| String::from("out of physical frames while loading ELF"))?;
+            unsafe {
+                mapper
+                    .map_to(page, frame, flags, frame_allocator)
+                    .map_err(|e| format!("failed to map PT_LOAD segment: {:?}", e))?
+                    .flush();
+            }
+            // Zero the frame, then copy in the file-backed bytes that fall
+            // within this page (the remainder — up to p_memsz — is .bss).
+            zero_and_fill(frame, page, phdr, bytes)?;
+        }
+    }
+
+    let stack_top = VirtAddr::new(USER_STACK_TOP);
+    let stack_bottom = stack_top - USER_STACK_SIZE;
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+    for page in Page::<Size4KiB>::range_inclusive(
+        Page::containing_address(stack_bottom),
+        Page::containing_address(stack_top - 1u64),
+    ) {
+        let frame = frame_allocator
+            .allocate_frame()
+            .ok_or_else(|| String::from("out of physical frames for user stack"))?;
+        unsafe {
+            mapper
+                .map_to(page, frame, flags, frame_allocator)
+                .map_err(|e| format!("failed to map user stack: {:?}", e))?
+                .flush();
+        }
+    }
+
+    regions.push((stack_bottom.as_u64(), stack_top.as_u64()));
+
+    Ok(UserProgram {
+        entry: VirtAddr::new(ehdr.e_entry),
+        stack_top,
+        regions,
+    })
+}
+
+fn zero_and_fill(
+    frame: x86_64::structures::paging::PhysFrame<Size4KiB>,
+    page: Page<Size4KiB>,
+    phdr: &Elf64Phdr,
+    file: &[u8],
+) -> Result<(), String> {
+    // The frame is only reachable once mapped; writing through its physical
+    // address requires the kernel's direct physical-memory mapping, which
+    // `crate::memory` already maintains for frame allocation bookkeeping.
+    let phys: PhysAddr = frame.start_address();
+    let dst = unsafe { crate::memory::phys_to_virt(phys).as_mut_ptr::<u8>() };
+    unsafe { core::ptr::write_bytes(dst, 0, 4096) };
+
+    let page_start = page.start_address().as_u64();
+    let seg_start = phdr.p_vaddr;
+    let seg_file_end = seg_start + phdr.p_filesz;
+    let page_end = page_start + 4096;
+
+    let copy_start = core::cmp::max(page_start, seg_start);
+    let copy_end = core::cmp::min(page_end, seg_file_end);
+    if copy_start < copy_end {
+        let file_off = (phdr.p_offset + (copy_start - seg_start)) as usize;
+        let len = (copy_end - copy_start) as usize;
+        let file_bytes = file
+            .get(file_off..file_off + len)
+            .ok_or_else(|| String::from("segment file range out of bounds"))?;
+        let page_off = (copy_start - page_start) as usize;
+        unsafe {
+            core::ptr::copy_nonoverlapping(file_bytes.as_ptr(), dst.add(page_off), len);
+        }
+    }
+    Ok(())
+}
+
+/// Kernel stack pointer saved just before dropping to Ring 3, so the `exit`
+/// syscall can restore it and `ret` straight back into `run`, making this
+/// call look like an ordinary (blocking) function call from the caller's
+/// point of view.
+static KERNEL_RESUME_RSP: AtomicU64 = AtomicU64::new(0);
+/// Scratch buffer the `write` and `mem_recall` syscalls append into; read
+/// back by `run` once the program exits.
+static mut SYSCALL_OUTPUT: Vec<u8> = Vec::new();
+
+/// Run a loaded tool program to completion and return everything appended
+/// to its output, in syscall order: bytes passed to `write`, and the value
+/// of every successful `mem_recall` (which is not copied into the program's
+/// own memory). Blocks (on this core) until the program calls `exit`, the
+/// only path back into this function; a fault raised by the program is not
+/// caught here.
+pub fn run(program: &UserProgram, args: &[u8], host: &mut impl HostFunctions) -> Result<Vec<u8>, String> {
+    unsafe {
+        SYSCALL_OUTPUT = Vec::new();
+        CURRENT_HOST = Some(host as &mut dyn HostFunctions as *mut dyn HostFunctions);
+        CURRENT_REGIONS = program.regions.clone();
+    }
+
+    // Copy `args` onto the top of the user stack so the program can find
+    // them at a known offset (argc/argv-by-convention: length then bytes).
+    let args_addr = program.stack_top - 4096u64;
+    unsafe {
+        let dst = args_addr.as_mut_ptr::<u8>();
+        core::ptr::write_unaligned(dst as *mut u32, args.len() as u32);
+        core::ptr::copy_nonoverlapping(args.as_ptr(), dst.add(4), args.len());
+    }
+
+    unsafe { enter_user_mode(program.entry, program.stack_top - 4096u64, args_addr) };
+
+    unsafe {
+        CURRENT_HOST = None;
+        CURRENT_REGIONS = Vec::new();
+    }
+    Ok(unsafe { core::mem::take(&mut SYSCALL_OUTPUT) })
+}
+
+/// Type-erased pointer to the `HostFunctions` impl for the in-flight call,
+/// so the (necessarily free) syscall handler can reach it. Single-core,
+/// single in-flight tool call at a time — matches the rest of the kernel's
+/// cooperative, non-reentrant run loop, so a plain `static mut` (rather
+/// than an atomic) is sufficient here.
+static mut CURRENT_HOST: Option<*mut dyn HostFunctions> = None;
+
+/// `[start, end)` ranges the in-flight tool is allowed to pass pointers
+/// into — set by `run` alongside `CURRENT_HOST`, same single-core,
+/// non-reentrant reasoning applies.
+static mut CURRENT_REGIONS: Vec<(u64, u64)> = Vec::new();
+
+/// Drop from Ring 0 to Ring 3 and jump to `entry` with `rsp` set to
+/// `stack_top`, passing `args_ptr` in `rdi`.
+///
+/// `#[naked]`, like `syscall_entry` below, and for the same reason: an
+/// ordinary function's compiler-generated prologue (`push rbp; mov rbp,
+/// rsp; sub rsp, N`) may run before any Rust statement of ours does, so
+/// `rsp` read mid-body is not reliably the address `call` pushed a return
+/// address at. Naked functions skip that prologue entirely, so the very
+/// first instruction here sees `rsp` exactly as our caller's `call` left
+/// it — the one stack pointer the later `exit` syscall's `mov rsp, ...;
+/// ret` can safely resume onto.
+#[naked]
+unsafe extern "C" fn enter_user_mode(entry: VirtAddr, stack_top: VirtAddr, args_ptr: VirtAddr) {
+    use crate::gdt::{USER_CODE_SELECTOR, USER_DATA_SELECTOR};
+    use x86_64::registers::rflags::RFlags;
+
+    core::arch::asm!(
+        // Capture the resume point before touching anything else — rdi,
+        // rsi, rdx hold entry/stack_top/args_ptr (System V `extern "C"`)
+        // and aren't clobbered yet.
+        "mov [rip + {resume}], rsp",
+
+        // Move the incoming args somewhere the iretq frame setup below
+        // won't step on before we're done reading them.
+        "mov r8, rdi",
+        "mov r9, rsi",
+        "mov r10, rdx",
+
+        "movzx eax, word ptr [rip + {ucs}]",
+        "mov r11, rax",
+        "movzx eax, word ptr [rip + {uds}]",
+
+        "mov rdi, r10",   // args_ptr -> rdi, the user program's argument
+        "push rax",       // ss
+        "push r9",        // stack_top
+        "push {rflags}",
+        "push r11",       // cs
+        "push r8",        // entry
+        "iretq",
+        resume = sym KERNEL_RESUME_RSP,
+        ucs = sym USER_CODE_SELECTOR,
+        uds = sym USER_DATA_SELECTOR,
+        rflags = const RFlags::INTERRUPT_FLAG.bits(),
+        options(noreturn),
+    );
+}
+
+/// `int 0x80` entry point. Registered directly in the IDT (not through the
+/// `x86_64` crate's typed `extern "x86-interrupt"` closures, which only hand
+/// back an `InterruptStackFrame` and can't expose `rax`/`rdi`/`rsi`/`rdx`/
+/// `r10`) at privilege level 3 by `crate::interrupts::init_idt`.
+///
+/// `#[naked]` so the body is exactly the asm below: save every caller-saved
+/// GP register except `rax` (the ones a call to `dispatch_syscall` may
+/// clobber; it preserves the callee-saved ones itself),
+/// shuffle `rax`/`rdi`/`rsi`/`rdx`/`r10` into the System V call convention
+/// `dispatch_syscall` expects, call it, restore everything except `rax`
+/// (which now holds the syscall's return value), and `iretq` back to Ring 3.
+/// `dispatch_syscall`'s `EXIT` arm never returns here — it switches `rsp`
+/// back to the saved kernel stack and `ret`s straight into `run` instead.
+#[naked]
+pub unsafe extern "C" fn syscall_entry() {
+    core::arch::asm!(
+        "push rcx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r11",
+        "mov r8, r10",  // arg3 -> 5th call arg
+        "mov rcx, rdx", // arg2 -> 4th call arg
+        "mov rdx, rsi", // arg1 -> 3rd call arg
+        "mov rsi, rdi", // arg0 -> 2nd call arg
+        "mov rdi, rax", // num  -> 1st call arg
+        "call {dispatch}",
+        "pop r11",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rcx",
+        "iretq",
+        dispatch = sym dispatch_syscall,
+        options(noreturn),
+    );
+}
+
+/// Shared dispatch body `syscall_entry` calls with the decoded registers
+/// (`num`, `arg0`, `arg1`, `arg2`, `arg3`). Returns the value to load into
+/// `rax` before `iretq` — or never returns, for `exit`.
+///
+/// # Safety
+/// Must only be called from the Ring 3 trap path with `CURRENT_HOST` and
+/// `CURRENT_REGIONS` set by an in-flight `run`.
+pub unsafe extern "C" fn dispatch_syscall(num: u64, arg0: u64, arg1: u64, arg2: u64, arg3: u64) -> u64 {
+    match num {
+        syscall::WRITE => {
+            let len = arg1 as usize;
+            if !validate_user_range(arg0, len) {
+                return u64::MAX;
+            }
+            let bytes = core::slice::from_raw_parts(arg0 as *const u8, len);
+            SYSCALL_OUTPUT.extend_from_slice(bytes);
+            0
+        }
+        syscall::MEM_STORE => {
+            let key = match read_user_str(arg0, arg1 as usize) {
+                Some(k) => k,
+                None => return u64::MAX,
+            };
+            let value = match read_user_str(arg2, arg3 as usize) {
+                Some(v) => v,
+                None => return u64::MAX,
+            };
+            if let Some(host) = current_host() {
+                host.mem_store(&key, &value);
+            }
+            0
+        }
+        syscall::MEM_RECALL => {
+            let key = match read_user_str(arg0, arg1 as usize) {
+                Some(k) => k,
+                None => return u64::MAX,
+            };
+            match current_host().and_then(|h| h.mem_recall(&key)) {
+                Some(value) => {
+                    SYSCALL_OUTPUT.extend_from_slice(value.as_bytes());
+                    value.len() as u64
+                }
+                None => u64::MAX,
+            }
+        }
+        syscall::EXIT => {
+            let resume_rsp = KERNEL_RESUME_RSP.load(Ordering::SeqCst);
+            core::arch::asm!(
+                "mov rsp, {rsp}",
+                "ret",
+                rsp = in(reg) resume_rsp,
+                options(noreturn),
+            );
+        }
+        _ => u64::MAX,
+    }
+}
+
+/// Check that a user-supplied `[ptr, ptr+len)` range lies entirely within
+/// one of the in-flight tool's mapped regions. A merely-buggy (not just
+/// malicious) tool handing a bad pointer to a syscall gets `u64::MAX` back
+/// from that syscall rather than making the kernel dereference arbitrary or
+/// unmapped memory from inside the handler.
+fn validate_user_range(ptr: u64, len: usize) -> bool {
+    let end = match ptr.checked_add(len as u64) {
+        Some(e) => e,
+        None => return false,
+    };
+    unsafe { CURRENT_REGIONS.iter().any(|&(start, stop)| ptr >= start && end <= stop) }
+}
+
+unsafe fn read_user_str(ptr: u64, len: usize) -> Option<String> {
+    if !validate_user_range(ptr, len) {
+        return None;
+    }
+    let bytes = core::slice::from_raw_parts(ptr as *const u8, len);
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+unsafe fn current_host() -> Option<&'static mut dyn HostFunctions> {
+    CURRENT_HOST.map(|ptr| &mut *ptr)
+}
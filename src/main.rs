@@ -43,12 +43,14 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! {
     use x86_64::VirtAddr;
 
     let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset.into_option().unwrap());
-    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mapper = unsafe { memory::init(phys_mem_offset) };
     let mut frame_allocator =
         unsafe { memory::BootInfoFrameAllocator::init(&boot_info.memory_map) };
 
-    // Initialize the heap allocator
-    llm_os::allocator::init_heap(&mut mapper, &mut frame_allocator)
+    // Initialize the heap allocator, through the arch-neutral PageMapper
+    // seam rather than x86_64 paging types directly.
+    let mut arch_mapper = llm_os::arch::x86_64_impl::X86PageMapper::new(mapper);
+    llm_os::allocator::init_heap(&mut arch_mapper, &mut frame_allocator)
         .expect("heap initialization failed");
     serial_println!("[kernel] Heap allocator initialized");
 
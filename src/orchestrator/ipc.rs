@@ -0,0 +1,32 @@
+//! Typed IPC between agents — inboxes and capability-gated sending.
+//!
+//! Every agent the orchestrator spawns gets an `AgentId` and an inbox. An
+//! agent may only send to a peer it holds a `CapToken` for; a worker that
+//! was never granted a capability to message the planner can't spam it, no
+//! matter what it puts in its output.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+/// Identifies an agent within an `Orchestrator`.
+pub type AgentId = u32;
+
+/// Proof that the holder is allowed to send to `target`. Capabilities are
+/// granted by the orchestrator (see `Orchestrator::grant`), not by agents
+/// themselves — an agent can't mint itself access to a new peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapToken {
+    pub target: AgentId,
+}
+
+/// One message in flight between two agents.
+#[derive(Debug, Clone)]
+pub struct Envelope {
+    pub from: AgentId,
+    pub to: AgentId,
+    pub capability: CapToken,
+    pub payload: String,
+}
+
+/// A single agent's inbox — messages waiting to be delivered to it.
+pub type Inbox = VecDeque<Envelope>;
@@ -0,0 +1,181 @@
+//! Orchestrator — multiplexes inference cycles across several agents.
+//!
+//! `lib.rs` describes "Scheduler → Orchestrator (multiplexes inference
+//! cycles across tasks)," but until now only a single `Agent` ever existed.
+//! This module spawns multiple `Agent` instances (e.g. a planner and
+//! specialized workers) and routes messages between them over capability-
+//! gated IPC channels (see `ipc`), so a task can be delegated across several
+//! context windows instead of cramming everything into one.
+//!
+//! Routing runs as a cooperative round-robin: pull one envelope per
+//! non-empty inbox per pass, hand its payload to the target agent's
+//! `process_input`, and route any `/send <agent> <msg>` the response
+//! produces back into the IPC system — repeating until every inbox drains.
+
+pub mod ipc;
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::format;
+use crate::agent::Agent;
+use crate::serial_println;
+use ipc::{AgentId, CapToken, Envelope, Inbox};
+
+/// Multiple agents, their inboxes, and the capability grants governing who
+/// may message whom.
+pub struct Orchestrator {
+    agents: BTreeMap<AgentId, Agent>,
+    inboxes: BTreeMap<AgentId, Inbox>,
+    /// Capabilities held by each agent: the set of peers it's allowed to
+    /// send to.
+    grants: BTreeMap<AgentId, Vec<CapToken>>,
+    next_id: AgentId,
+}
+
+impl Orchestrator {
+    pub fn new() -> Self {
+        Orchestrator {
+            agents: BTreeMap::new(),
+            inboxes: BTreeMap::new(),
+            grants: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Boot and register a new agent, returning its id.
+    pub fn spawn(&mut self, mut agent: Agent) -> AgentId {
+        agent.boot();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.agents.insert(id, agent);
+        self.inboxes.insert(id, Inbox::new());
+        serial_println!("[orchestrator] spawned agent {}", id);
+        id
+    }
+
+    /// Grant `from` the capability to send messages to `to`. Without this,
+    /// `send(from, to, ...)` is rejected.
+    pub fn grant(&mut self, from: AgentId, to: AgentId) {
+        self.grants.entry(from).or_insert_with(Vec::new).push(CapToken { target: to });
+    }
+
+    /// Enqueue a message from `from` to `to`, if `from` holds a capability
+    /// for `to`.
+    pub fn send(&mut self, from: AgentId, to: AgentId, payload: String) -> Result<(), String> {
+        let capability = self
+            .grants
+            .get(&from)
+            .and_then(|caps| caps.iter().find(|c| c.target == to))
+            .copied()
+            .ok_or_else(|| format!("agent {} has no capability to message agent {}", from, to))?;
+
+        let inbox = self
+            .inboxes
+            .get_mut(&to)
+            .ok_or_else(|| format!("agent {} does not exist", to))?;
+        inbox.push_back(Envelope { from, to, capability, payload });
+        Ok(())
+    }
+
+    /// Seed the first message into an agent's inbox to kick off a run —
+    /// bypasses the capability check since it originates outside the agent
+    /// graph (e.g. the user's initial task).
+    pub fn post(&mut self, to: AgentId, payload: String) -> Result<(), String> {
+        let inbox = self
+            .inboxes
+            .get_mut(&to)
+            .ok_or_else(|| format!("agent {} does not exist", to))?;
+        inbox.push_back(Envelope {
+            from: to,
+            to,
+            capability: CapToken { target: to },
+            payload,
+        });
+        Ok(())
+    }
+
+    /// Drain every inbox: each pass, pop one envelope per non-empty inbox,
+    /// hand its payload to that agent, and route any `/send` command the
+    /// response contains back into the IPC system. Repeats until nothing is
+    /// left to deliver.
+    pub fn run(&mut self) {
+        loop {
+            let ids: Vec<AgentId> = self.inboxes.keys().copied().collect();
+            let mut delivered_any = false;
+
+            for id in ids {
+                let envelope = match self.inboxes.get_mut(&id).and_then(|inbox| inbox.pop_front()) {
+                    Some(e) => e,
+                    None => continue,
+                };
+                delivered_any = true;
+
+                serial_println!(
+                    "[orchestrator] delivering {} -> {} ({} bytes)",
+                    envelope.from,
+                    envelope.to,
+                    envelope.payload.len()
+                );
+
+                let response = match self.agents.get_mut(&id) {
+                    Some(agent) => agent.process_input(&envelope.payload),
+                    None => continue,
+                };
+
+                if let Some((target, message)) = parse_send_command(&response) {
+                    if let Err(e) = self.send(id, target, message) {
+                        serial_println!("[orchestrator] dropped message from agent {}: {}", id, e);
+                    }
+                }
+            }
+
+            if !delivered_any {
+                break;
+            }
+        }
+    }
+}
+
+/// Parse a `/send <agent_id> <message>` command out of an agent's response.
+/// This is the tool surface workers use to hand results back (e.g. to the
+/// planner) — `Agent::process_input` doesn't know about the orchestrator,
+/// so routing is the orchestrator's job once it sees the command in the
+/// response text.
+fn parse_send_command(response: &str) -> Option<(AgentId, String)> {
+    let rest = response.strip_prefix("/send ")?;
+    let mut parts = rest.splitn(2, ' ');
+    let target: AgentId = parts.next()?.parse().ok()?;
+    let message = parts.next().unwrap_or("");
+    Some((target, String::from(message)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Without a grant, `send` must reject — a worker can't mint itself
+    /// access to a peer just by naming it in a `/send` command.
+    #[test_case]
+    fn send_without_capability_is_rejected() {
+        let mut orchestrator = Orchestrator::new();
+        let from = orchestrator.spawn(Agent::new());
+        let to = orchestrator.spawn(Agent::new());
+
+        let result = orchestrator.send(from, to, String::from("hello"));
+
+        assert!(result.is_err());
+    }
+
+    #[test_case]
+    fn send_with_capability_is_delivered() {
+        let mut orchestrator = Orchestrator::new();
+        let from = orchestrator.spawn(Agent::new());
+        let to = orchestrator.spawn(Agent::new());
+        orchestrator.grant(from, to);
+
+        let result = orchestrator.send(from, to, String::from("hello"));
+
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+//! Inference-cycle tracing — lightweight instrumentation for where an agent
+//! turn spends its time.
+//!
+//! There was previously no visibility into this at all. `trace_span!`
+//! records a timestamped span (using the CPU timestamp counter as the
+//! clock) for each phase of `Agent::process_input` — the context push, each
+//! `ToolRegistry::execute`, and `ContextWindow` eviction events — into a
+//! bounded ring buffer. The `/trace` command dumps the most recent spans
+//! with durations and per-tool token cost, making the "CPU → LLM /
+//! Scheduler" analogy in the architecture notes something you can actually
+//! profile.
+
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+
+/// Ring buffer capacity — bounded the same way `ContextWindow` bounds its
+/// own message history, so tracing itself can't grow the heap unboundedly.
+const RING_CAPACITY: usize = 64;
+
+/// One completed span: a labeled phase of an agent turn, its start/end
+/// timestamp-counter readings, and any token-cost delta it attributes to
+/// itself (e.g. a tool's estimated cost).
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+    pub label: String,
+    pub start_tsc: u64,
+    pub end_tsc: u64,
+    pub tokens_delta: i64,
+}
+
+impl TraceEvent {
+    pub fn duration_tsc(&self) -> u64 {
+        self.end_tsc.saturating_sub(self.start_tsc)
+    }
+}
+
+/// Single-core and cooperative, same as the rest of the agent run loop —
+/// there's no preemption for a plain `static mut` to race against, so no
+/// lock is needed here any more than one is needed around `ToolRegistry`.
+static mut TRACE_RING: Option<VecDeque<TraceEvent>> = None;
+
+fn ring() -> &'static mut VecDeque<TraceEvent> {
+    unsafe {
+        if TRACE_RING.is_none() {
+            TRACE_RING = Some(VecDeque::with_capacity(RING_CAPACITY));
+        }
+        TRACE_RING.as_mut().unwrap()
+    }
+}
+
+/// Record a completed span, evicting the oldest entry once the ring is
+/// full — the same bounded-eviction shape `ContextWindow` uses for messages.
+pub fn record(event: TraceEvent) {
+    let ring = ring();
+    if ring.len() >= RING_CAPACITY {
+        ring.pop_front();
+    }
+    ring.push_back(event);
+}
+
+/// Read the CPU timestamp counter. This is the tracer's clock.
+pub fn read_tsc() -> u64 {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        return core::arch::x86_64::_rdtsc();
+    }
+    #[cfg(target_arch = "riscv64")]
+    unsafe {
+        let cycles: u64;
+        core::arch::asm!("rdcycle {}", out(reg) cycles);
+        return cycles;
+    }
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "riscv64")))]
+    0
+}
+
+/// An open span. Recorded into the trace ring when it drops, so callers
+/// just let the guard fall out of scope at the end of the phase they're
+/// measuring rather than calling an explicit `end()`.
+pub struct Span {
+    label: String,
+    start_tsc: u64,
+    tokens_delta: i64,
+}
+
+impl Span {
+    pub fn start(label: String) -> Self {
+        Span {
+            label,
+            start_tsc: read_tsc(),
+            tokens_delta: 0,
+        }
+    }
+
+    /// Attach a token-cost delta to this span before it closes (e.g. a
+    /// tool's estimated token cost).
+    pub fn set_tokens_delta(&mut self, tokens_delta: i64) {
+        self.tokens_delta = tokens_delta;
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        record(TraceEvent {
+            label: core::mem::take(&mut self.label),
+            start_tsc: self.start_tsc,
+            end_tsc: read_tsc(),
+            tokens_delta: self.tokens_delta,
+        });
+    }
+}
+
+/// Start a trace span that records itself on drop.
+///
+/// `trace_span!("label")` for a static label, or `trace_span!("label",
+/// value)` to fold a runtime value (e.g. a tool name) into the recorded
+/// label — used as `let _span = trace_span!("tool_exec", name);`.
+#[macro_export]
+macro_rules! trace_span {
+    ($label:expr) => {
+        $crate::tracer::Span::start(alloc::string::String::from($label))
+    };
+    ($label:expr, $value:expr) => {
+        $crate::tracer::Span::start(alloc::format!("{}:{}", $label, $value))
+    };
+}
+
+/// Format the last `n` spans (most recent first) with durations and
+/// per-span token cost — backs the `/trace` command.
+pub fn dump(n: usize) -> String {
+    let ring = ring();
+    if ring.is_empty() {
+        return String::from("No trace events recorded yet.");
+    }
+
+    let mut out = format!("Last {} trace events (most recent first):\n", n.min(ring.len()));
+    for event in ring.iter().rev().take(n) {
+        out.push_str(&format!(
+            "  {:<24} {:>12} tsc   tokens Δ{}\n",
+            event.label,
+            event.duration_tsc(),
+            event.tokens_delta
+        ));
+    }
+    out
+}